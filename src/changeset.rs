@@ -0,0 +1,110 @@
+//! Binary encoding for the row-level changes captured by `DataAccessor::capture_changeset` and
+//! replayed by `DataAccessor::apply_changeset`.
+//!
+//! A change is recorded as a (table, row primary key, column, old value, new value) tuple
+//! rather than a full before/after row, so a changeset stays small even for a batch of writes
+//! that only ever touch a couple of columns per row.
+
+use gcontributor::error::SQLDataError;
+use gcontributor::types::SqlResult;
+
+/// One recorded change to a single column of a single row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeRecord {
+    pub table_name: String,
+    pub row_key: String,
+    pub column_name: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// Serializes `records` into the binary changeset format `decode` expects back.
+pub fn encode(records: &[ChangeRecord]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(records.len() as u32).to_be_bytes());
+    for record in records {
+        write_str(&mut out, &record.table_name);
+        write_str(&mut out, &record.row_key);
+        write_str(&mut out, &record.column_name);
+        write_opt_str(&mut out, record.old_value.as_deref());
+        write_opt_str(&mut out, record.new_value.as_deref());
+    }
+    out
+}
+
+/// Parses a changeset produced by `encode` back into its `ChangeRecord`s.
+pub fn decode(blob: &[u8]) -> SqlResult<Vec<ChangeRecord>> {
+    let mut cursor = Cursor::new(blob);
+    let count = cursor.read_u32()?;
+    // Not `Vec::with_capacity(count as usize)`: `count` is an untrusted u32 read straight off
+    // the blob, so a corrupt/crafted one (e.g. 0xFFFFFFFF) would otherwise trigger a
+    // multi-hundred-GB allocation before a single byte is validated to exist.
+    let mut records = Vec::new();
+    for _ in 0..count {
+        records.push(ChangeRecord {
+            table_name: cursor.read_str()?,
+            row_key: cursor.read_str()?,
+            column_name: cursor.read_str()?,
+            old_value: cursor.read_opt_str()?,
+            new_value: cursor.read_opt_str()?,
+        });
+    }
+    Ok(records)
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u32).to_be_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_opt_str(out: &mut Vec<u8>, s: Option<&str>) {
+    match s {
+        Some(s) => {
+            out.push(1);
+            write_str(out, s);
+        }
+        None => out.push(0),
+    }
+}
+
+/// Minimal big-endian cursor over a changeset blob that reports a malformed-changeset error
+/// instead of panicking when the blob is truncated or corrupt.
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Cursor { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> SqlResult<&'a [u8]> {
+        let end = self.pos + len;
+        let slice = self.data.get(self.pos..end).ok_or_else(|| {
+            SQLDataError::not_found("bytes", format!("{len} at offset {}", self.pos), "changeset")
+        })?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn read_u32(&mut self) -> SqlResult<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn read_str(&mut self) -> SqlResult<String> {
+        let len = self.read_u32()? as usize;
+        let bytes = self.take(len)?;
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    fn read_opt_str(&mut self) -> SqlResult<Option<String>> {
+        let tag = self.take(1)?[0];
+        if tag == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(self.read_str()?))
+        }
+    }
+}
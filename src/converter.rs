@@ -0,0 +1,13 @@
+//! Converter turns a source image into the commit schedule used to recreate it as a
+//! contribution graph.
+
+use chrono::NaiveDate;
+
+pub trait Converter {
+    /// Reports the image format this converter handles (e.g. `"jpeg"`)
+    fn convert(&self) -> &'static str;
+
+    /// Re-derives a `(date, commit_count)` schedule from raw image bytes of the format this
+    /// converter handles, so a stored plan can be checked for drift against its source image
+    fn regenerate_plan(&self, image: &[u8]) -> Vec<(NaiveDate, u32)>;
+}
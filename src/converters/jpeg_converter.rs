@@ -1,3 +1,5 @@
+use chrono::NaiveDate;
+
 use crate::converter::Converter;
 
 pub struct JpegConverter {
@@ -12,4 +14,11 @@ impl JpegConverter {
 
 impl Converter for JpegConverter {
   fn convert(&self) -> &'static str {"jpeg"}
+
+  fn regenerate_plan(&self, _image: &[u8]) -> Vec<(NaiveDate, u32)> {
+    // TODO: decode JPEG pixels into a date/commit-count schedule. Until this returns something
+    // real, FlowControl::verify_plan will report every persisted commit_plan date as drift
+    // (regenerated is always empty), so it can't yet be relied on to detect anything.
+    Vec::new()
+  }
 }
\ No newline at end of file
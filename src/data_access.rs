@@ -5,11 +5,20 @@
 //! and the number of commits required to build the desired image on that date.
 
 use sqlite;
-use chrono::{Local, NaiveDate};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::{fs, vec::Vec};
 
+use crate::changeset::{self, ChangeRecord};
+use crate::from_row::{ChangelogRow, CommitPlanRow, FromRow, SourceImageRow, UserRow};
+use crate::statement_cache::StatementCache;
+use crate::transaction::Transaction;
+use gcontributor::types::BackupProgress;
 use gcontributor::types::CommitDict;
+use gcontributor::types::ConflictAction;
 use gcontributor::error::SQLDataError;
 use gcontributor::types::SqlResult;
 
@@ -17,25 +26,80 @@ use gcontributor::types::SqlResult;
 /// By default this is stored in resources/gcontrib.db
 pub struct DataAccessor {
     db_location: PathBuf,
-    timeout: usize,
+    pragmas: Vec<(String, String)>,
+    /// A persistent connection plus its prepared-statement cache, lazily opened on the first
+    /// call that goes through `with_cached_statement` so `setup_tables` can still run against
+    /// its own short-lived connections during `build()`.
+    cache: RefCell<Option<StatementCache>>,
+}
+
+/// Builds a `DataAccessor` with non-default connection settings. Use `DataAccessor::new`/
+/// `DataAccessor::with_db` directly when the defaults (WAL, `synchronous=NORMAL`,
+/// `foreign_keys=ON`) are fine.
+pub struct DataAccessorBuilder {
+    db_location: PathBuf,
+    pragmas: Vec<(String, String)>,
+}
+
+impl DataAccessorBuilder {
+    /// Starts a builder at the default db location with the default pragmas
+    pub fn new() -> Self {
+        DataAccessorBuilder {
+            db_location: Path::new(DataAccessor::DEFAULT_DB_PATH).join(DataAccessor::DEFAULT_DB_NAME),
+            pragmas: DataAccessor::default_pragmas(),
+        }
+    }
+
+    /// Overrides the database file location
+    pub fn with_db<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.db_location = path.as_ref().to_path_buf();
+        self
+    }
+
+    /// Replaces the PRAGMAs applied right after opening every connection, before any
+    /// statement is prepared against it. Defaults to `journal_mode=WAL`,
+    /// `synchronous=NORMAL`, `foreign_keys=ON`.
+    pub fn with_pragmas(mut self, pragmas: Vec<(String, String)>) -> Self {
+        self.pragmas = pragmas;
+        self
+    }
+
+    /// Builds the `DataAccessor`, creating its tables if they don't already exist
+    pub fn build(self) -> SqlResult<DataAccessor> {
+        let da = DataAccessor {
+            db_location: self.db_location,
+            pragmas: self.pragmas,
+            cache: RefCell::new(None),
+        };
+        da.setup_tables()?;
+        Ok(da)
+    }
+}
+
+impl Default for DataAccessorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 /// Data Accessor implementation containing the operations for interacting with the persistent
 /// storage db
 impl DataAccessor {
     pub const DEFAULT_DB_NAME: &'static str = "gcontrib.db";
     pub const DEFAULT_DB_PATH: &'static str = "resources";
-    pub const DEFAULT_MS_TIMEOUT: usize = 1_000;
 
     /// MAX retries on a busy connection with the database
     const MAX_BUSY_RETRIES: usize = 5;
     /// The multiplier at which the busy retry waits in between connection attempts to the db
     const BUSY_WAIT_MULT_MS: u64 = 100;
 
-    const USERS_USERNAME_INDEX:usize = 0;
-    const USERS_REPO_INDEX:usize = 1;
-    const COMMIT_COUNT_INDEX:usize = 1;
-    const COMMIT_ISRUN_INDEX:usize = 2;
-    //const COMMIT_DATE_INDEX:usize = 0;
+    /// Number of pages copied per `sqlite3_backup_step` call made by `backup_to`/`restore_from`
+    const BACKUP_STEP_PAGES: i32 = 16;
+
+    /// Chunk size used when streaming a source image into/out of its BLOB column
+    const IMAGE_BLOB_CHUNK_BYTES: usize = 64 * 1024;
+
+    /// Default number of prepared statements kept warm in the persistent connection's cache
+    const STATEMENT_CACHE_CAPACITY: usize = 16;
 
     /// Creates the user table
     pub const QCREATE_USER_T: &'static str = "
@@ -80,6 +144,15 @@ impl DataAccessor {
             date = :date
         ;";
 
+    /// Gets every row of the commit_plan table
+    pub const QGET_ALL_COMMIT_PLAN: &'static str = "
+        SELECT
+            date
+            ,commit_count
+            ,is_run
+        FROM commit_plan
+        ;";
+
     /// Updates the is_run field for a given date
     pub const QUPDATE_COMMIT_DATE: &'static str = "
         UPDATE commit_plan
@@ -102,25 +175,119 @@ impl DataAccessor {
         SELECT name FROM users
         ;";
 
+    /// Creates the source image table
+    pub const QCREATE_SOURCE_IMAGE_T: &'static str = "
+        CREATE TABLE IF NOT EXISTS source_image (
+            id INTEGER PRIMARY KEY
+            ,format TEXT NOT NULL
+            ,width INTEGER NOT NULL
+            ,height INTEGER NOT NULL
+            ,created_at TEXT NOT NULL
+            ,image_data BLOB NOT NULL
+        );";
+
+    /// Inserts source image metadata plus a zero-filled placeholder blob of `:len` bytes,
+    /// to be filled in afterwards via `open_image_blob`
+    pub const QADD_SOURCE_IMAGE: &'static str = "
+        INSERT INTO source_image(format, width, height, created_at, image_data)
+        VALUES(
+            :format,
+            :width,
+            :height,
+            :created_at,
+            zeroblob(:len)
+        )
+        RETURNING id;";
+
+    /// Gets the metadata for the most recently stored source image
+    pub const QGET_LATEST_SOURCE_IMAGE: &'static str = "
+        SELECT
+            id
+            ,format
+            ,width
+            ,height
+            ,created_at
+        FROM source_image
+        ORDER BY id DESC
+        LIMIT 1;";
+
+    /// Records every INSERT/UPDATE on `commit_plan`/`users`, one row per changed column, so
+    /// `capture_changeset` has something to read without hooking into SQLite's session API
+    pub const QCREATE_CHANGELOG_T: &'static str = "
+        CREATE TABLE IF NOT EXISTS changelog (
+            id INTEGER PRIMARY KEY AUTOINCREMENT
+            ,table_name TEXT NOT NULL
+            ,row_key TEXT NOT NULL
+            ,column_name TEXT NOT NULL
+            ,old_value TEXT
+            ,new_value TEXT
+            ,changed_at TEXT NOT NULL
+        );";
+
+    /// Logs every `commit_plan` column change into `changelog`
+    pub const QCREATE_COMMIT_PLAN_CHANGELOG_TRIGGERS: &'static str = "
+        CREATE TRIGGER IF NOT EXISTS trg_commit_plan_changelog_ai AFTER INSERT ON commit_plan BEGIN
+            INSERT INTO changelog(table_name, row_key, column_name, old_value, new_value, changed_at)
+            VALUES ('commit_plan', NEW.date, 'commit_count', NULL, CAST(NEW.commit_count AS TEXT), datetime('now'));
+            INSERT INTO changelog(table_name, row_key, column_name, old_value, new_value, changed_at)
+            VALUES ('commit_plan', NEW.date, 'is_run', NULL, CAST(NEW.is_run AS TEXT), datetime('now'));
+        END;
+        CREATE TRIGGER IF NOT EXISTS trg_commit_plan_changelog_au AFTER UPDATE ON commit_plan BEGIN
+            INSERT INTO changelog(table_name, row_key, column_name, old_value, new_value, changed_at)
+            SELECT 'commit_plan', NEW.date, 'commit_count', CAST(OLD.commit_count AS TEXT), CAST(NEW.commit_count AS TEXT), datetime('now')
+            WHERE OLD.commit_count IS NOT NEW.commit_count;
+            INSERT INTO changelog(table_name, row_key, column_name, old_value, new_value, changed_at)
+            SELECT 'commit_plan', NEW.date, 'is_run', CAST(OLD.is_run AS TEXT), CAST(NEW.is_run AS TEXT), datetime('now')
+            WHERE OLD.is_run IS NOT NEW.is_run;
+        END;";
+
+    /// Logs every `users` column change into `changelog`
+    pub const QCREATE_USERS_CHANGELOG_TRIGGERS: &'static str = "
+        CREATE TRIGGER IF NOT EXISTS trg_users_changelog_ai AFTER INSERT ON users BEGIN
+            INSERT INTO changelog(table_name, row_key, column_name, old_value, new_value, changed_at)
+            VALUES ('users', NEW.name, 'repo', NULL, NEW.repo, datetime('now'));
+        END;
+        CREATE TRIGGER IF NOT EXISTS trg_users_changelog_au AFTER UPDATE ON users BEGIN
+            INSERT INTO changelog(table_name, row_key, column_name, old_value, new_value, changed_at)
+            SELECT 'users', NEW.name, 'repo', OLD.repo, NEW.repo, datetime('now')
+            WHERE OLD.repo IS NOT NEW.repo;
+        END;";
+
+    /// Gets every changelog entry recorded at or after `:since`, oldest first. `changed_at` is
+    /// only second-resolution (`datetime('now')`), so this has to be an inclusive `>=`: a write
+    /// landing in the same wall-clock second as `:since` would otherwise be silently dropped
+    /// from the changeset.
+    pub const QGET_CHANGELOG_SINCE: &'static str = "
+        SELECT
+            table_name
+            ,row_key
+            ,column_name
+            ,old_value
+            ,new_value
+        FROM changelog WHERE
+            changed_at >= :since
+        ORDER BY id ASC;";
+
     /// Constructs a new DataAccessor and sets up tables at the default location
     pub fn new() -> SqlResult<Self> {
-        let da = DataAccessor {
-            db_location: Path::new(Self::DEFAULT_DB_PATH).to_path_buf().join(Self::DEFAULT_DB_NAME),
-            timeout: Self::DEFAULT_MS_TIMEOUT,
-        };
-        da.setup_tables()?;
-        Ok(da)
+        DataAccessorBuilder::new().build()
     }
 
     /// Provides the ability to create a DataAccessor with a defined Path
     pub fn with_db<P: AsRef<Path>>(path: P) -> SqlResult<Self> {
         // TODO understand if this is copying the data
-        let da = DataAccessor {
-            db_location: path.as_ref().to_path_buf(),
-            timeout: Self::DEFAULT_MS_TIMEOUT,
-        };
-        da.setup_tables()?;
-        Ok(da)
+        DataAccessorBuilder::new().with_db(path).build()
+    }
+
+    /// The PRAGMAs applied by `new`/`with_db`: WAL journaling so the scheduler loop and a
+    /// concurrent planner can read/write without blocking each other, `NORMAL` durability
+    /// (safe under WAL), and `foreign_keys` enforcement
+    fn default_pragmas() -> Vec<(String, String)> {
+        vec![
+            ("journal_mode".to_string(), "WAL".to_string()),
+            ("synchronous".to_string(), "NORMAL".to_string()),
+            ("foreign_keys".to_string(), "ON".to_string()),
+        ]
     }
 
     /// Actually creates the database and tables within
@@ -128,6 +295,8 @@ impl DataAccessor {
         self.create_ifnot_parent_dir()?;
         self.create_commit_t()?;
         self.create_user_t()?;
+        self.create_source_image_t()?;
+        self.create_changelog_t()?;
         Ok(())
     }
 
@@ -136,39 +305,43 @@ impl DataAccessor {
         &self.db_location
     }
 
-    /// Adds a commit plan to the commit table
-    pub fn add_commit_plan(&self, commits: &CommitDict) -> SqlResult<()> {
-        let conn = self.setup_connection()?;
-        conn.execute("BEGIN IMMEDIATE;")?;
-        { // this scope is added to ensure the stmt drop is finalized
-            let mut stmt = conn.prepare(Self::QADD_COMMIT)?;
-            for (&date, &count) in commits {
-                let date_str = date.to_string();
-                stmt.bind((":date", date_str.as_str()))?;
-                stmt.bind((":commit_count", count as i64))?;
-                stmt.bind((":is_run", 0))?;
-                stmt.next()?;
-                stmt.reset()?;
-            }
+    /// Opens an immediate transaction on a dedicated connection. Writes made through `txn`
+    /// are only durable once `txn.commit()` is called; dropping it without committing rolls
+    /// everything back.
+    pub fn transaction(&self) -> SqlResult<Transaction> {
+        Transaction::begin(self.setup_connection()?)
+    }
+
+    /// Adds a commit plan to the commit table within `txn`, so a batch of plan writes either
+    /// all land or all roll back together
+    pub fn add_commit_plan(&self, txn: &Transaction, commits: &CommitDict) -> SqlResult<()> {
+        let mut stmt = txn.connection().prepare(Self::QADD_COMMIT)?;
+        for (&date, &count) in commits {
+            let date_str = date.to_string();
+            stmt.bind((":date", date_str.as_str()))?;
+            stmt.bind((":commit_count", count as i64))?;
+            stmt.bind((":is_run", 0))?;
+            stmt.next()?;
+            stmt.reset()?;
         }
-        conn.execute("COMMIT;")?;
         Ok(())
     }
 
     /// Returns a commit count for the specific date given
     pub fn get_date_count(&self, date: NaiveDate) -> SqlResult<u32> {
-        let conn = self.setup_connection()?;
-        let mut stmt = conn.prepare(Self::QGET_DATE_DATA)?;
-        stmt.bind((":date", date.to_string().as_str()))?;
-        match stmt.next()? {
-            sqlite::State::Row => {
-                let res = stmt.read::<i64, _>(Self::COMMIT_COUNT_INDEX)? as u32;
-                return Ok(res);
-            },
-            sqlite::State::Done => {
-                Err(SQLDataError::not_found("count", date.to_string(), "commit_plan"))
-            }
-        }
+        let row: CommitPlanRow = self.query_one(
+            Self::QGET_DATE_DATA,
+            &[(":date", sqlite::Value::String(date.to_string()))],
+            "count",
+            date.to_string(),
+            "commit_plan",
+        )?;
+        Ok(row.commit_count)
+    }
+
+    /// Returns every row currently stored in the commit_plan table
+    pub fn get_all_commit_plan(&self) -> SqlResult<Vec<CommitPlanRow>> {
+        self.query_all(Self::QGET_ALL_COMMIT_PLAN, &[])
     }
 
     /// Returns true if there is an current commit plan stored and false otherwise
@@ -176,75 +349,79 @@ impl DataAccessor {
         // TODO include user data in this to make sure there is a repository to commit to
         let now = Local::now();
         let date = now.date_naive();
-        let conn = self.setup_connection()?;
-
-        let mut stmt = conn.prepare(Self::QGET_DATE_DATA)?;
-        stmt.bind((":date", date.to_string().as_str()))?;
-
-        match stmt.next()? {
-            sqlite::State::Row => {
-                return Ok(true)
-            },
-            sqlite::State::Done => {
-                return Ok(false)
-            }
-        }
+        let rows: Vec<CommitPlanRow> = self.query_all(
+            Self::QGET_DATE_DATA,
+            &[(":date", sqlite::Value::String(date.to_string()))],
+        )?;
+        Ok(!rows.is_empty())
     }
 
     /// Returns the repository currently stored as the upstream commit destitination
     pub fn get_repo(&self, username: &str) -> SqlResult<String> {
-        let conn = self.setup_connection()?;
-
-        let mut stmt = conn.prepare(Self::QGET_REPO_URL)?;
-        stmt.bind((":username", username))?;
-
-        match stmt.next()? {
-            sqlite::State::Row => {
-                return Ok(stmt.read::<String, _>(Self::USERS_REPO_INDEX)?);
-            },
-            sqlite::State::Done => {
-                Err(SQLDataError::not_found("url", username, "users"))
-            }
-        }
+        let row: UserRow = self.query_one(
+            Self::QGET_REPO_URL,
+            &[(":username", sqlite::Value::String(username.to_string()))],
+            "url",
+            username,
+            "users",
+        )?;
+        Ok(row.repo)
     }
 
     /// Returns an Array of usernames
     pub fn get_users(&self) -> SqlResult<Vec<String>> {
-        let conn = self.setup_connection()?;
-        let mut stmt = conn.prepare(Self::QGET_USERS)?;
-        let mut usernames: Vec<String> = Vec::new();
-
-        loop {
-            match stmt.next()? {
-                sqlite::State::Done => {
-                    break;
-                }
-                sqlite::State::Row => {
-                    usernames.push(stmt.read::<String, _>(Self::USERS_USERNAME_INDEX)?);
-                }
-            }
-        }
-        Ok(usernames)
+        let rows: Vec<(String,)> = self.query_all(Self::QGET_USERS, &[])?;
+        Ok(rows.into_iter().map(|(name,)| name).collect())
     }
 
     /// Returns true if a commit plan has ran for a specific date and false otherwise
     pub fn has_run(&self, date: NaiveDate) -> SqlResult<bool> {
-        let conn = self.setup_connection()?;
-        let mut stmt = conn.prepare(Self::QGET_DATE_DATA)?;
-        stmt.bind((":date", date.to_string().as_str()))?;
+        let rows: Vec<CommitPlanRow> = self.query_all(
+            Self::QGET_DATE_DATA,
+            &[(":date", sqlite::Value::String(date.to_string()))],
+        )?;
+        Ok(rows.first().map_or(false, |row| row.is_run))
+    }
 
-        if let sqlite::State::Row = stmt.next()? {
-            if stmt.read::<i64, _>(Self::COMMIT_ISRUN_INDEX)? == 1 {
-                return Ok(true);
+    /// Prepares `sql`, binds each named `params` entry, and collects every result row as `T`.
+    /// The statement is pulled from this `DataAccessor`'s statement cache instead of being
+    /// re-prepared on a fresh connection each call.
+    pub fn query_all<T: FromRow>(&self, sql: &str, params: &[(&str, sqlite::Value)]) -> SqlResult<Vec<T>> {
+        self.with_cached_statement(sql, |stmt| {
+            for (name, value) in params {
+                stmt.bind((*name, value.clone()))?;
             }
-        }
-        Ok(false)
+
+            let mut rows = Vec::new();
+            loop {
+                match stmt.next()? {
+                    sqlite::State::Done => break,
+                    sqlite::State::Row => rows.push(T::from_row(stmt)?),
+                }
+            }
+            Ok(rows)
+        })
     }
 
-    /// Writes to the database that a commit count was run for a specific date
-    pub fn set_run(&self, date: NaiveDate) -> SqlResult<()> {
-        let conn = self.setup_connection()?;
-        let mut stmt = conn.prepare(Self::QUPDATE_COMMIT_DATE)?;
+    /// Like `query_all`, but expects exactly one row and reports `item`/`name`/`table` in the
+    /// `SQLDataError::ErrNoEnt` raised when the query comes back empty
+    pub fn query_one<T: FromRow>(
+        &self,
+        sql: &str,
+        params: &[(&str, sqlite::Value)],
+        item: &'static str,
+        name: impl Into<String>,
+        table: &'static str,
+    ) -> SqlResult<T> {
+        self.query_all(sql, params)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| SQLDataError::not_found(item, name, table))
+    }
+
+    /// Writes to the database, within `txn`, that a commit count was run for a specific date
+    pub fn set_run(&self, txn: &Transaction, date: NaiveDate) -> SqlResult<()> {
+        let mut stmt = txn.connection().prepare(Self::QUPDATE_COMMIT_DATE)?;
         stmt.bind((":is_run", 1))?;
         stmt.bind((":date", date.to_string().as_str()))?;
         match stmt.next()? {
@@ -262,10 +439,258 @@ impl DataAccessor {
         }
     }
 
-    /// Add User and related info
-    pub fn add_user_info(&self, username: &str, repo: &str) -> SqlResult<()> {
+    /// Copies the live database out to `dest` a handful of pages at a time using SQLite's
+    /// online backup API, so a run that is still writing to `commit_plan`/`users` doesn't
+    /// have to be paused. `progress` is invoked after every step with the pages left to copy.
+    pub fn backup_to<P: AsRef<Path>>(&self, dest: P, progress: impl FnMut(BackupProgress)) -> SqlResult<()> {
+        Self::create_ifnot_parent_dir_of(dest.as_ref())?;
+        let src_conn = self.setup_connection()?;
+        let dest_conn = sqlite::open(dest.as_ref())?;
+        Self::run_backup(&src_conn, &dest_conn, progress)
+    }
+
+    /// Restores `src` into this database using the same online, page-by-page backup API as
+    /// [`DataAccessor::backup_to`], so a concurrent reader against this db is not corrupted.
+    pub fn restore_from<P: AsRef<Path>>(&self, src: P, progress: impl FnMut(BackupProgress)) -> SqlResult<()> {
+        let src_conn = sqlite::open(src.as_ref())?;
+        let dest_conn = self.setup_connection()?;
+        Self::run_backup(&src_conn, &dest_conn, progress)
+    }
+
+    /// Drives an `sqlite3_backup` handle to completion, stepping `BACKUP_STEP_PAGES` pages at
+    /// a time and sleeping on `SQLITE_BUSY`/`SQLITE_LOCKED` via `db_busy_handler`. The handle is
+    /// always finalized, even when a step fails partway through.
+    fn run_backup(src_conn: &sqlite::Connection, dest_conn: &sqlite::Connection, mut progress: impl FnMut(BackupProgress)) -> SqlResult<()> {
+        let main = CString::new("main").unwrap();
+        unsafe {
+            let backup = sqlite::ffi::sqlite3_backup_init(
+                dest_conn.as_raw(),
+                main.as_ptr(),
+                src_conn.as_raw(),
+                main.as_ptr(),
+            );
+            if backup.is_null() {
+                return Err(Self::last_error(dest_conn.as_raw()));
+            }
+
+            let mut retry_num: usize = 0;
+            let result = loop {
+                let rc = sqlite::ffi::sqlite3_backup_step(backup, Self::BACKUP_STEP_PAGES);
+                progress(BackupProgress {
+                    remaining: sqlite::ffi::sqlite3_backup_remaining(backup),
+                    total: sqlite::ffi::sqlite3_backup_pagecount(backup),
+                });
+
+                match rc {
+                    sqlite::ffi::SQLITE_DONE => break Ok(()),
+                    sqlite::ffi::SQLITE_OK => continue,
+                    sqlite::ffi::SQLITE_BUSY | sqlite::ffi::SQLITE_LOCKED => {
+                        retry_num += 1;
+                        if Self::db_busy_handler(retry_num) {
+                            continue;
+                        }
+                        break Err(Self::last_error(dest_conn.as_raw()));
+                    }
+                    _ => break Err(Self::last_error(dest_conn.as_raw())),
+                }
+            };
+
+            sqlite::ffi::sqlite3_backup_finish(backup);
+            result
+        }
+    }
+
+    /// Reads the most recent error code/message off a raw connection handle, for the raw
+    /// backup API calls that don't go through the `sqlite` crate's own `Result` wrapping.
+    unsafe fn last_error(conn: *mut sqlite::ffi::sqlite3) -> SQLDataError {
+        let code = sqlite::ffi::sqlite3_errcode(conn) as isize;
+        let message = std::ffi::CStr::from_ptr(sqlite::ffi::sqlite3_errmsg(conn))
+            .to_string_lossy()
+            .into_owned();
+        SQLDataError::from(sqlite::Error { code: Some(code), message: Some(message) })
+    }
+
+    /// Opens `column` of the row `row_id` in `table` as an incremental `Blob`, via the raw
+    /// `sqlite3_blob_open` FFI since the `sqlite` crate has no incremental blob I/O of its own.
+    fn open_blob_raw<'a>(
+        conn: &'a sqlite::Connection,
+        table: &str,
+        column: &str,
+        row_id: i64,
+        read_only: bool,
+    ) -> SqlResult<Blob<'a>> {
+        let db = CString::new("main").unwrap();
+        let table = CString::new(table).unwrap();
+        let column = CString::new(column).unwrap();
+        unsafe {
+            let mut handle: *mut sqlite::ffi::sqlite3_blob = std::ptr::null_mut();
+            let rc = sqlite::ffi::sqlite3_blob_open(
+                conn.as_raw(),
+                db.as_ptr(),
+                table.as_ptr(),
+                column.as_ptr(),
+                row_id,
+                if read_only { 0 } else { 1 },
+                &mut handle,
+            );
+            if rc != sqlite::ffi::SQLITE_OK {
+                return Err(Self::last_error(conn.as_raw()));
+            }
+            let size = sqlite::ffi::sqlite3_blob_bytes(handle);
+            Ok(Blob { conn, handle, pos: 0, size })
+        }
+    }
+
+    /// Stores the source image that a commit plan was (or will be) generated from: inserts its
+    /// metadata plus a zero-filled placeholder blob, then streams `data` into that blob in
+    /// `IMAGE_BLOB_CHUNK_BYTES` pieces so the whole image never has to sit in memory at once.
+    /// Returns the new row's id.
+    pub fn add_source_image(&self, format: &str, width: u32, height: u32, len: usize, mut data: impl Read) -> SqlResult<i64> {
+        let conn = self.setup_connection()?;
+        let row_id = {
+            let mut stmt = conn.prepare(Self::QADD_SOURCE_IMAGE)?;
+            stmt.bind((":format", format))?;
+            stmt.bind((":width", width as i64))?;
+            stmt.bind((":height", height as i64))?;
+            stmt.bind((":created_at", Local::now().to_rfc3339().as_str()))?;
+            stmt.bind((":len", len as i64))?;
+            match stmt.next()? {
+                sqlite::State::Row => stmt.read::<i64, _>(0)?,
+                sqlite::State::Done => {
+                    return Err(SQLDataError::not_set("source image", format, "source_image"));
+                }
+            }
+        };
+
+        let mut blob = Self::open_blob_raw(&conn, "source_image", "image_data", row_id, false)?;
+        let mut buf = vec![0u8; Self::IMAGE_BLOB_CHUNK_BYTES];
+        loop {
+            let n = data.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            blob.write_all(&buf[..n])?;
+        }
+        Ok(row_id)
+    }
+
+    /// Returns the metadata of the most recently stored source image
+    pub fn get_latest_source_image(&self) -> SqlResult<SourceImageRow> {
+        self.query_one(Self::QGET_LATEST_SOURCE_IMAGE, &[], "source image", "latest", "source_image")
+    }
+
+    /// Opens the `image_data` BLOB of the source_image row `row_id` for incremental reads
+    /// (`read_only = true`) or writes, and hands it to `f`. Incremental blob I/O lets callers
+    /// stream large images through without ever holding the whole thing in memory.
+    pub fn open_image_blob<T>(
+        &self,
+        row_id: i64,
+        read_only: bool,
+        f: impl FnOnce(&mut Blob<'_>) -> SqlResult<T>,
+    ) -> SqlResult<T> {
         let conn = self.setup_connection()?;
-        let mut stmt = conn.prepare(Self::QADD_USER)?;
+        let mut blob = Self::open_blob_raw(&conn, "source_image", "image_data", row_id, read_only)?;
+        f(&mut blob)
+    }
+
+    /// Records every `commit_plan`/`users` column change logged since `since` into a binary
+    /// changeset that `apply_changeset` can replay on another database. `since` is compared
+    /// against `changelog.changed_at`, which is stamped with SQLite's UTC `datetime('now')`,
+    /// so pass a UTC-naive timestamp (e.g. `Utc::now().naive_utc()`)
+    pub fn capture_changeset(&self, since: NaiveDateTime) -> SqlResult<Vec<u8>> {
+        let since_str = since.format("%Y-%m-%d %H:%M:%S").to_string();
+        let rows: Vec<ChangelogRow> = self.query_all(
+            Self::QGET_CHANGELOG_SINCE,
+            &[(":since", sqlite::Value::String(since_str))],
+        )?;
+        let records: Vec<ChangeRecord> = rows
+            .into_iter()
+            .map(|row| ChangeRecord {
+                table_name: row.table_name,
+                row_key: row.row_key,
+                column_name: row.column_name,
+                old_value: row.old_value,
+                new_value: row.new_value,
+            })
+            .collect();
+        Ok(changeset::encode(&records))
+    }
+
+    /// Replays a changeset captured by `capture_changeset` into this database inside a single
+    /// transaction, resolving any `is_run` disagreement between the changeset and what's
+    /// already stored locally via `on_conflict`. Under `ConflictAction::KeepLocal` every incoming
+    /// `is_run` value is discarded, so a `commit_plan` row that only the changeset has is created
+    /// locally with `is_run = 0`.
+    pub fn apply_changeset(&self, blob: &[u8], on_conflict: ConflictAction) -> SqlResult<()> {
+        let records = changeset::decode(blob)?;
+        let txn = self.transaction()?;
+        for record in &records {
+            Self::apply_change_record(&txn, record, on_conflict)?;
+        }
+        txn.commit()
+    }
+
+    /// Applies one `ChangeRecord`, ensuring the target row exists first since a changeset can
+    /// replay an UPDATE to a row the destination database has never seen
+    fn apply_change_record(txn: &Transaction, record: &ChangeRecord, on_conflict: ConflictAction) -> SqlResult<()> {
+        match (record.table_name.as_str(), record.column_name.as_str()) {
+            ("commit_plan", "commit_count") => {
+                Self::ensure_commit_plan_row(txn, &record.row_key)?;
+                Self::set_row_column(txn, "commit_plan", "date", &record.row_key, "commit_count", record.new_value.as_deref())
+            }
+            ("commit_plan", "is_run") => {
+                Self::ensure_commit_plan_row(txn, &record.row_key)?;
+                if on_conflict == ConflictAction::KeepLocal {
+                    return Ok(());
+                }
+                Self::set_row_column(txn, "commit_plan", "date", &record.row_key, "is_run", record.new_value.as_deref())
+            }
+            ("users", "repo") => {
+                Self::ensure_user_row(txn, &record.row_key)?;
+                Self::set_row_column(txn, "users", "name", &record.row_key, "repo", record.new_value.as_deref())
+            }
+            _ => Err(SQLDataError::not_set("column", record.column_name.clone(), "changeset")),
+        }
+    }
+
+    /// Inserts a default `commit_plan` row for `date_key` if one doesn't already exist
+    fn ensure_commit_plan_row(txn: &Transaction, date_key: &str) -> SqlResult<()> {
+        let mut stmt = txn
+            .connection()
+            .prepare("INSERT INTO commit_plan(date, commit_count, is_run) VALUES(:date, 0, 0) ON CONFLICT(date) DO NOTHING;")?;
+        stmt.bind((":date", date_key))?;
+        stmt.next()?;
+        Ok(())
+    }
+
+    /// Inserts a default `users` row for `name_key` if one doesn't already exist
+    fn ensure_user_row(txn: &Transaction, name_key: &str) -> SqlResult<()> {
+        let mut stmt = txn
+            .connection()
+            .prepare("INSERT INTO users(name, repo) VALUES(:name, '') ON CONFLICT(name) DO NOTHING;")?;
+        stmt.bind((":name", name_key))?;
+        stmt.next()?;
+        Ok(())
+    }
+
+    /// Sets `column` of the row keyed by `pk_column = pk_value` in `table`. `table`/`pk_column`/
+    /// `column` always come from the fixed match arms in `apply_change_record`, never from
+    /// changeset contents, so interpolating them into the SQL text is safe.
+    fn set_row_column(txn: &Transaction, table: &str, pk_column: &str, pk_value: &str, column: &str, value: Option<&str>) -> SqlResult<()> {
+        let sql = format!("UPDATE {table} SET {column} = :value WHERE {pk_column} = :pk;");
+        let mut stmt = txn.connection().prepare(sql)?;
+        stmt.bind((":pk", pk_value))?;
+        match value {
+            Some(v) => stmt.bind((":value", v))?,
+            None => stmt.bind((":value", sqlite::Value::Null))?,
+        };
+        stmt.next()?;
+        Ok(())
+    }
+
+    /// Add User and related info within `txn`
+    pub fn add_user_info(&self, txn: &Transaction, username: &str, repo: &str) -> SqlResult<()> {
+        let mut stmt = txn.connection().prepare(Self::QADD_USER)?;
         stmt.bind((":username", username))?;
         stmt.bind((":repo_uri", repo))?;
 
@@ -279,14 +704,75 @@ impl DataAccessor {
         }
     }
 
+    /// Runs `f` against a reset, already-prepared statement for `sql`, lazily opening this
+    /// `DataAccessor`'s persistent connection and its statement cache on first use
+    fn with_cached_statement<T>(
+        &self,
+        sql: &str,
+        f: impl FnOnce(&mut sqlite::Statement<'static>) -> SqlResult<T>,
+    ) -> SqlResult<T> {
+        let mut slot = self.cache.borrow_mut();
+        if slot.is_none() {
+            *slot = Some(StatementCache::new(self.setup_connection()?, Self::STATEMENT_CACHE_CAPACITY));
+        }
+        let cache = slot.as_mut().expect("just populated above");
+        f(cache.prepare(sql)?)
+    }
+
+    /// Drops every statement this `DataAccessor` has cached, so the next query for each SQL
+    /// text is re-prepared from scratch. The persistent connection itself stays open.
+    pub fn flush_cache(&self) {
+        if let Some(cache) = self.cache.borrow_mut().as_mut() {
+            cache.flush();
+        }
+    }
+
     /// returns a connection the database with a preset timeout and handler
     fn setup_connection(&self) -> SqlResult<sqlite::Connection> {
         let mut conn = sqlite::open(&self.db_location)?;
-        conn.set_busy_timeout(self.timeout)?;
         conn.set_busy_handler(Self::db_busy_handler)?;
+        for (name, value) in &self.pragmas {
+            Self::validate_pragma_name(name)?;
+            Self::validate_pragma_value(value)?;
+            conn.execute(format!("PRAGMA {name} = {value};"))?;
+        }
         Ok(conn)
     }
 
+    /// Rejects anything but a bare ASCII identifier (letters/digits/underscore, not starting
+    /// with a digit), since `setup_connection` interpolates pragma names directly into
+    /// `PRAGMA ... = ...;` SQL text with no way to bind it as a parameter. `with_pragmas` is
+    /// public API, so a caller building pairs from config/CLI input must not be able to smuggle
+    /// a second statement (e.g. `busy_timeout = 1; DROP TABLE secret; --`) through here.
+    fn validate_pragma_name(s: &str) -> SqlResult<()> {
+        let mut chars = s.chars();
+        let starts_ok = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+        let rest_ok = chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if starts_ok && rest_ok {
+            Ok(())
+        } else {
+            Err(SQLDataError::InvalidIdentifier(s.to_string()))
+        }
+    }
+
+    /// Rejects anything but a bare ASCII identifier or a (possibly negative) integer, the only
+    /// pragma value shapes `default_pragmas` and the sqlite docs actually need. Same
+    /// interpolated-with-no-binding hazard as `validate_pragma_name`.
+    fn validate_pragma_value(s: &str) -> SqlResult<()> {
+        let unsigned = s.strip_prefix('-').unwrap_or(s);
+        let is_identifier = {
+            let mut chars = s.chars();
+            chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        };
+        let is_integer = !unsigned.is_empty() && unsigned.chars().all(|c| c.is_ascii_digit());
+        if is_identifier || is_integer {
+            Ok(())
+        } else {
+            Err(SQLDataError::InvalidIdentifier(s.to_string()))
+        }
+    }
+
     /// Defines a handler in case the database is busy and sets to retry the connection
     fn db_busy_handler(retry_num: usize) -> bool {
         if retry_num > Self::MAX_BUSY_RETRIES {
@@ -300,7 +786,13 @@ impl DataAccessor {
 
     /// Generates the parent directories of the database
     fn create_ifnot_parent_dir(&self) -> SqlResult<()> {
-        if let Some(parent) = self.db_location.parent() {
+        Self::create_ifnot_parent_dir_of(&self.db_location)
+    }
+
+    /// Creates the parent directory of `path` if it doesn't already exist, so opening a
+    /// fresh sqlite connection against `path` doesn't fail with "unable to open database file".
+    fn create_ifnot_parent_dir_of(path: &Path) -> SqlResult<()> {
+        if let Some(parent) = path.parent() {
             if !parent.as_os_str().is_empty() {
                 fs::create_dir_all(parent)?;
             }
@@ -323,6 +815,82 @@ impl DataAccessor {
         stmt.next()?;
         Ok(())
     }
+
+    /// Creates the source image table
+    fn create_source_image_t(&self) -> SqlResult<()> {
+        let conn = self.setup_connection()?;
+        let mut stmt = conn.prepare(Self::QCREATE_SOURCE_IMAGE_T)?;
+        stmt.next()?;
+        Ok(())
+    }
+
+    /// Creates the changelog table and the triggers that keep it populated
+    fn create_changelog_t(&self) -> SqlResult<()> {
+        let conn = self.setup_connection()?;
+        conn.execute(Self::QCREATE_CHANGELOG_T)?;
+        conn.execute(Self::QCREATE_COMMIT_PLAN_CHANGELOG_TRIGGERS)?;
+        conn.execute(Self::QCREATE_USERS_CHANGELOG_TRIGGERS)?;
+        Ok(())
+    }
+}
+
+/// A `sqlite3_blob` opened for incremental I/O, borrowed from the `Connection` it was opened
+/// against. The `sqlite` crate has no equivalent of its own, so this goes straight to the raw
+/// `sqlite3_blob_*` FFI, tracking a cursor position like a file so `Read`/`Write` can be used
+/// the same way callers already use them against any other stream.
+pub struct Blob<'a> {
+    conn: &'a sqlite::Connection,
+    handle: *mut sqlite::ffi::sqlite3_blob,
+    pos: i32,
+    size: i32,
+}
+
+impl<'a> Read for Blob<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = (self.size - self.pos).max(0) as usize;
+        let n = buf.len().min(remaining);
+        if n == 0 {
+            return Ok(0);
+        }
+        let rc = unsafe {
+            sqlite::ffi::sqlite3_blob_read(self.handle, buf.as_mut_ptr() as *mut _, n as i32, self.pos)
+        };
+        if rc != sqlite::ffi::SQLITE_OK {
+            return Err(std::io::Error::new(std::io::ErrorKind::Other, unsafe { DataAccessor::last_error(self.conn.as_raw()) }));
+        }
+        self.pos += n as i32;
+        Ok(n)
+    }
+}
+
+impl<'a> Write for Blob<'a> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let remaining = (self.size - self.pos).max(0) as usize;
+        let n = buf.len().min(remaining);
+        if n == 0 {
+            return Ok(0);
+        }
+        let rc = unsafe {
+            sqlite::ffi::sqlite3_blob_write(self.handle, buf.as_ptr() as *const _, n as i32, self.pos)
+        };
+        if rc != sqlite::ffi::SQLITE_OK {
+            return Err(std::io::Error::new(std::io::ErrorKind::Other, unsafe { DataAccessor::last_error(self.conn.as_raw()) }));
+        }
+        self.pos += n as i32;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> Drop for Blob<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            sqlite::ffi::sqlite3_blob_close(self.handle);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -402,6 +970,35 @@ mod test {
         assert!(table_exists(&conn, "users"));
     }
 
+    /// Reads back the current value of a `PRAGMA` as text
+    fn pragma_value(conn: &sqlite::Connection, name: &str) -> String {
+        let mut stmt = conn.prepare(format!("PRAGMA {name};")).unwrap();
+        match stmt.next().unwrap() {
+            State::Row => stmt.read::<String, _>(0).unwrap(),
+            State::Done => panic!("PRAGMA {name} returned no row"),
+        }
+    }
+
+    #[test]
+    fn test_default_pragmas_enable_wal() {
+        let db_loc = create_random_db_loc().unwrap();
+        let da = DataAccessor::with_db(db_loc).unwrap();
+        let conn = sqlite::open(da.db_path()).unwrap();
+        assert_eq!(pragma_value(&conn, "journal_mode").to_lowercase(), "wal");
+    }
+
+    #[test]
+    fn test_builder_with_pragmas_overrides_defaults() {
+        let db_loc = create_random_db_loc().unwrap();
+        let da = DataAccessorBuilder::new()
+            .with_db(db_loc)
+            .with_pragmas(vec![("journal_mode".to_string(), "DELETE".to_string())])
+            .build()
+            .unwrap();
+        let conn = sqlite::open(da.db_path()).unwrap();
+        assert_eq!(pragma_value(&conn, "journal_mode").to_lowercase(), "delete");
+    }
+
     #[test]
     fn test_set_run() {
         // TODO make this a set date
@@ -413,13 +1010,18 @@ mod test {
 
 
         let cd = CommitDict::from([(&day, 3)]);
-        da.add_commit_plan(&cd).unwrap();
+        let txn = da.transaction().unwrap();
+        da.add_commit_plan(&txn, &cd).unwrap();
+        txn.commit().unwrap();
 
         let conn = sqlite::open(da.db_path()).unwrap();
         assert!(!date_has_run(&conn, day));
-        da.set_run(day).unwrap();
+
+        let txn = da.transaction().unwrap();
+        da.set_run(&txn, day).unwrap();
+        txn.commit().unwrap();
         assert!(date_has_run(&conn, day));
-        
+
     }
 
     #[test]
@@ -436,24 +1038,184 @@ mod test {
 
         let db_loc = create_random_db_loc().unwrap();
         let da = DataAccessor::with_db(db_loc).unwrap();
-        da.add_commit_plan(&cd).unwrap();
+        let txn = da.transaction().unwrap();
+        da.add_commit_plan(&txn, &cd).unwrap();
+        txn.commit().unwrap();
 
         let conn = sqlite::open(da.db_path()).unwrap();
         assert_eq!(commit_plan_written(&conn), 3);
     }
 
+    #[test]
+    fn test_backup_and_restore_round_trip() {
+        let day = NaiveDate::from_ymd_opt(1980, 4, 2).unwrap();
+        let cd = CommitDict::from([(&day, 5)]);
+
+        let src_loc = create_random_db_loc().unwrap();
+        let src = DataAccessor::with_db(src_loc).unwrap();
+        let txn = src.transaction().unwrap();
+        src.add_commit_plan(&txn, &cd).unwrap();
+        txn.commit().unwrap();
+
+        let backup_loc = create_random_db_loc().unwrap();
+        let mut steps = 0;
+        src.backup_to(&backup_loc, |_progress| steps += 1).unwrap();
+        assert!(steps > 0);
+
+        let dest_loc = create_random_db_loc().unwrap();
+        let dest = DataAccessor::with_db(dest_loc).unwrap();
+        dest.restore_from(&backup_loc, |_progress| ()).unwrap();
+
+        assert_eq!(dest.get_date_count(day).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_source_image_round_trip() {
+        let db_loc = create_random_db_loc().unwrap();
+        let da = DataAccessor::with_db(db_loc).unwrap();
+
+        let image_bytes = vec![0xFFu8, 0xD8, 0xFF, 0xE0, 0x01, 0x02, 0x03];
+        let row_id = da
+            .add_source_image("jpeg", 64, 64, image_bytes.len(), image_bytes.as_slice())
+            .unwrap();
+
+        let meta = da.get_latest_source_image().unwrap();
+        assert_eq!(meta.id, row_id);
+        assert_eq!(meta.format, "jpeg");
+        assert_eq!(meta.width, 64);
+        assert_eq!(meta.height, 64);
+
+        let mut read_back = Vec::new();
+        da.open_image_blob(row_id, true, |blob| {
+            blob.read_to_end(&mut read_back)?;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(read_back, image_bytes);
+    }
+
+    #[test]
+    fn test_changeset_round_trip_take_remote() {
+        let day = NaiveDate::from_ymd_opt(1980, 4, 2).unwrap();
+        let cd = CommitDict::from([(&day, 3)]);
+
+        let src_loc = create_random_db_loc().unwrap();
+        let src = DataAccessor::with_db(src_loc).unwrap();
+        let since = chrono::Utc::now().naive_utc();
+        let txn = src.transaction().unwrap();
+        src.add_commit_plan(&txn, &cd).unwrap();
+        src.set_run(&txn, day).unwrap();
+        txn.commit().unwrap();
+
+        let blob = src.capture_changeset(since).unwrap();
+
+        let dest_loc = create_random_db_loc().unwrap();
+        let dest = DataAccessor::with_db(dest_loc).unwrap();
+        dest.apply_changeset(&blob, ConflictAction::TakeRemote).unwrap();
+
+        assert_eq!(dest.get_date_count(day).unwrap(), 3);
+        assert!(dest.has_run(day).unwrap());
+    }
+
+    #[test]
+    fn test_changeset_keep_local_preserves_local_is_run() {
+        let day = NaiveDate::from_ymd_opt(1980, 4, 2).unwrap();
+        let cd = CommitDict::from([(&day, 3)]);
+
+        let src_loc = create_random_db_loc().unwrap();
+        let src = DataAccessor::with_db(src_loc).unwrap();
+        let since = chrono::Utc::now().naive_utc();
+        let txn = src.transaction().unwrap();
+        src.add_commit_plan(&txn, &cd).unwrap();
+        src.set_run(&txn, day).unwrap();
+        txn.commit().unwrap();
+        let blob = src.capture_changeset(since).unwrap();
+
+        let dest_loc = create_random_db_loc().unwrap();
+        let dest = DataAccessor::with_db(dest_loc).unwrap();
+        let dest_txn = dest.transaction().unwrap();
+        dest.add_commit_plan(&dest_txn, &cd).unwrap();
+        dest_txn.commit().unwrap();
+        // dest never calls set_run, so is_run is locally false
+
+        dest.apply_changeset(&blob, ConflictAction::KeepLocal).unwrap();
+
+        assert!(!dest.has_run(day).unwrap());
+    }
+
+    #[test]
+    fn test_transaction_rolls_back_on_drop() {
+        let day = NaiveDate::from_ymd_opt(1980, 4, 2).unwrap();
+        let cd = CommitDict::from([(&day, 3)]);
+
+        let db_loc = create_random_db_loc().unwrap();
+        let da = DataAccessor::with_db(db_loc).unwrap();
+        {
+            let txn = da.transaction().unwrap();
+            da.add_commit_plan(&txn, &cd).unwrap();
+            // txn is dropped here without calling commit()
+        }
+
+        let conn = sqlite::open(da.db_path()).unwrap();
+        assert_eq!(commit_plan_written(&conn), 0);
+    }
+
+    #[test]
+    fn test_savepoint_rolls_back_without_aborting_transaction() {
+        let day1 = NaiveDate::from_ymd_opt(1980, 4, 2).unwrap();
+        let day2 = NaiveDate::from_ymd_opt(1980, 4, 3).unwrap();
+        let cd1 = CommitDict::from([(&day1, 3)]);
+        let cd2 = CommitDict::from([(&day2, 4)]);
+
+        let db_loc = create_random_db_loc().unwrap();
+        let da = DataAccessor::with_db(db_loc).unwrap();
+        let txn = da.transaction().unwrap();
+        da.add_commit_plan(&txn, &cd1).unwrap();
+        {
+            let _savepoint = txn.savepoint("plan_batch").unwrap();
+            da.add_commit_plan(&txn, &cd2).unwrap();
+            // savepoint is dropped here without calling release(), rolling cd2 back
+        }
+        txn.commit().unwrap();
+
+        let conn = sqlite::open(da.db_path()).unwrap();
+        assert_eq!(commit_plan_written(&conn), 1);
+    }
+
     #[test]
     fn test_get_repository() {
         let db_loc = create_random_db_loc().unwrap();
         let da = DataAccessor::with_db(db_loc).unwrap();
         let uri: &'static str = "uri://this.is.a.fake.address";
-        da.add_user_info("testuser1", uri).unwrap();
+        let txn = da.transaction().unwrap();
+        da.add_user_info(&txn, "testuser1", uri).unwrap();
+        txn.commit().unwrap();
         let users = da.get_users().unwrap();
         assert!(users.len() > 0);
         let repo_uri = da.get_repo(users[0].as_str()).unwrap();
         assert_eq!(repo_uri.as_str(), uri);
     }
 
+    #[test]
+    fn test_repeated_queries_reuse_cached_statement() {
+        let db_loc = create_random_db_loc().unwrap();
+        let da = DataAccessor::with_db(db_loc).unwrap();
+        let day = NaiveDate::from_ymd_opt(1980, 4, 2).unwrap();
+        let cd = CommitDict::from([(&day, 3)]);
+        let txn = da.transaction().unwrap();
+        da.add_commit_plan(&txn, &cd).unwrap();
+        txn.commit().unwrap();
+
+        // Calling get_date_count repeatedly re-binds the same cached, reset statement rather
+        // than re-preparing it, and should keep returning the same answer every time.
+        for _ in 0..3 {
+            assert_eq!(da.get_date_count(day).unwrap(), 3);
+        }
+
+        da.flush_cache();
+        assert_eq!(da.get_date_count(day).unwrap(), 3);
+    }
+
     #[test]
     #[should_panic]
     fn test_fail_no_user() {
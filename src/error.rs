@@ -22,6 +22,10 @@ pub enum SQLDataError {
     Sql(#[from] sqlite::Error),
     #[error(transparent)]
     Io(#[from] io::Error),
+    #[error(transparent)]
+    Parse(#[from] chrono::ParseError),
+    #[error("Invalid SQL identifier: {0:?} (expected ASCII alphanumeric/underscore, not starting with a digit)")]
+    InvalidIdentifier(String),
 }
 
 impl SQLDataError {
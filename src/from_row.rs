@@ -0,0 +1,116 @@
+//! Typed row decoding for `DataAccessor` queries.
+//!
+//! `FromRow` replaces the old pattern of hardcoding a column index per read and matching
+//! `State::Row`/`State::Done` by hand in every accessor method.
+
+use chrono::NaiveDate;
+use sqlite::{ReadableWithIndex as Readable, Statement};
+
+use gcontributor::types::SqlResult;
+
+/// Decodes one row of a `sqlite::Statement` into `Self`, columns read in select order.
+/// Implemented for tuples up to arity 4 and for the typed table rows below.
+pub trait FromRow: Sized {
+    fn from_row(stmt: &Statement) -> SqlResult<Self>;
+}
+
+impl<A: Readable> FromRow for (A,) {
+    fn from_row(stmt: &Statement) -> SqlResult<Self> {
+        Ok((stmt.read::<A, _>(0)?,))
+    }
+}
+
+impl<A: Readable, B: Readable> FromRow for (A, B) {
+    fn from_row(stmt: &Statement) -> SqlResult<Self> {
+        Ok((stmt.read::<A, _>(0)?, stmt.read::<B, _>(1)?))
+    }
+}
+
+impl<A: Readable, B: Readable, C: Readable> FromRow for (A, B, C) {
+    fn from_row(stmt: &Statement) -> SqlResult<Self> {
+        Ok((stmt.read::<A, _>(0)?, stmt.read::<B, _>(1)?, stmt.read::<C, _>(2)?))
+    }
+}
+
+impl<A: Readable, B: Readable, C: Readable, D: Readable> FromRow for (A, B, C, D) {
+    fn from_row(stmt: &Statement) -> SqlResult<Self> {
+        Ok((stmt.read::<A, _>(0)?, stmt.read::<B, _>(1)?, stmt.read::<C, _>(2)?, stmt.read::<D, _>(3)?))
+    }
+}
+
+/// A row of the `commit_plan` table: `date`, `commit_count`, `is_run`, in that column order.
+pub struct CommitPlanRow {
+    pub date: NaiveDate,
+    pub commit_count: u32,
+    pub is_run: bool,
+}
+
+impl FromRow for CommitPlanRow {
+    fn from_row(stmt: &Statement) -> SqlResult<Self> {
+        Ok(CommitPlanRow {
+            date: stmt.read::<String, _>(0)?.parse()?,
+            commit_count: stmt.read::<i64, _>(1)? as u32,
+            is_run: stmt.read::<i64, _>(2)? != 0,
+        })
+    }
+}
+
+/// A row of the `users` table: `name`, `repo`, in that column order.
+pub struct UserRow {
+    pub name: String,
+    pub repo: String,
+}
+
+impl FromRow for UserRow {
+    fn from_row(stmt: &Statement) -> SqlResult<Self> {
+        Ok(UserRow {
+            name: stmt.read::<String, _>(0)?,
+            repo: stmt.read::<String, _>(1)?,
+        })
+    }
+}
+
+/// Metadata for a row of the `source_image` table: `id`, `format`, `width`, `height`,
+/// `created_at`, in that column order. The `image_data` BLOB itself is read separately,
+/// incrementally, via `DataAccessor::open_image_blob`.
+pub struct SourceImageRow {
+    pub id: i64,
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+    pub created_at: String,
+}
+
+impl FromRow for SourceImageRow {
+    fn from_row(stmt: &Statement) -> SqlResult<Self> {
+        Ok(SourceImageRow {
+            id: stmt.read::<i64, _>(0)?,
+            format: stmt.read::<String, _>(1)?,
+            width: stmt.read::<i64, _>(2)? as u32,
+            height: stmt.read::<i64, _>(3)? as u32,
+            created_at: stmt.read::<String, _>(4)?,
+        })
+    }
+}
+
+/// A row of the `changelog` table: `table_name`, `row_key`, `column_name`, `old_value`,
+/// `new_value`, in that column order. Backs `DataAccessor::capture_changeset`.
+pub struct ChangelogRow {
+    pub table_name: String,
+    pub row_key: String,
+    pub column_name: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+impl FromRow for ChangelogRow {
+    fn from_row(stmt: &Statement) -> SqlResult<Self> {
+        Ok(ChangelogRow {
+            table_name: stmt.read::<String, _>(0)?,
+            row_key: stmt.read::<String, _>(1)?,
+            column_name: stmt.read::<String, _>(2)?,
+            old_value: stmt.read::<Option<String>, _>(3)?,
+            new_value: stmt.read::<Option<String>, _>(4)?,
+        })
+    }
+}
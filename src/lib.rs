@@ -9,4 +9,31 @@ pub mod types {
     pub type SqlResult<T> = std::result::Result<T, SQLDataError>;
 
     pub type CommitDict<'a> = HashMap<&'a NaiveDate, u32>;
+
+    /// Reports how far an online backup/restore has progressed, as measured in
+    /// SQLite pages. `remaining` reaches `0` once the copy is complete.
+    #[derive(Debug, Clone, Copy)]
+    pub struct BackupProgress {
+        pub remaining: i32,
+        pub total: i32,
+    }
+
+    /// A date where the persisted `commit_plan` count disagrees with what re-deriving the
+    /// plan from the stored source image would produce.
+    #[derive(Debug, Clone, Copy)]
+    pub struct PlanDrift {
+        pub date: NaiveDate,
+        pub expected: u32,
+        pub actual: u32,
+    }
+
+    /// Conflict policy used by `DataAccessor::apply_changeset` when a replayed `is_run` value
+    /// disagrees with what's already stored locally.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ConflictAction {
+        /// Keep the locally stored value, discarding the incoming one
+        KeepLocal,
+        /// Overwrite the local value with the incoming one
+        TakeRemote,
+    }
 }
@@ -5,15 +5,22 @@ mod converter;
 mod converters {
     pub mod jpeg_converter;
 }
+mod changeset;
 mod data_access;
+mod from_row;
+mod statement_cache;
+mod transaction;
 
-use chrono::{Local, NaiveTime, TimeZone};
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone};
+use std::collections::HashMap;
+use std::io::Read;
 use std::thread;
 use committer::*;
 use converter::*;
 use data_access::*;
 
 use crate::converters::jpeg_converter::JpegConverter;
+use gcontributor::types::{PlanDrift, SqlResult};
 
 pub struct FlowControl<C: Converter> {
     converter: C,
@@ -61,6 +68,41 @@ impl<C: Converter> FlowControl<C> {
         }
     }
 
+    /// Re-runs `converter` over the most recently stored source image and compares the result
+    /// against the persisted `commit_plan` in both directions: a date whose regenerated count
+    /// disagrees with what's stored, and a date that's stored but no longer produced by the
+    /// regenerated schedule at all, both count as drift.
+    pub fn verify_plan(&self) -> SqlResult<Vec<PlanDrift>> {
+        let image = self.data_accessor.get_latest_source_image()?;
+        let mut bytes = Vec::new();
+        self.data_accessor.open_image_blob(image.id, true, |blob| {
+            blob.read_to_end(&mut bytes)?;
+            Ok(())
+        })?;
+
+        let regenerated: HashMap<NaiveDate, u32> = self.converter.regenerate_plan(&bytes).into_iter().collect();
+        let persisted: HashMap<NaiveDate, u32> = self
+            .data_accessor
+            .get_all_commit_plan()?
+            .into_iter()
+            .map(|row| (row.date, row.commit_count))
+            .collect();
+
+        let mut drift = Vec::new();
+        for (&date, &expected) in &regenerated {
+            let actual = persisted.get(&date).copied().unwrap_or(0);
+            if actual != expected {
+                drift.push(PlanDrift { date, expected, actual });
+            }
+        }
+        for (&date, &actual) in &persisted {
+            if !regenerated.contains_key(&date) {
+                drift.push(PlanDrift { date, expected: 0, actual });
+            }
+        }
+        Ok(drift)
+    }
+
     fn run_commit(&self) -> Option<()> {
 
         Some(())
@@ -76,7 +118,78 @@ fn main() {
     let conv = JpegConverter::new();
     conv.convert();
     let da: DataAccessor = DataAccessor::new().unwrap();
-    let fc: FlowControl = FlowControl::new(conv, com, da);
+    let fc: FlowControl<JpegConverter> = FlowControl::new(conv, com, da);
     fc.run();
     println!("Hello, world!");
 }
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::*;
+    use gcontributor::types::CommitDict;
+
+    /// A `Converter` stub that hands back a fixed, caller-supplied schedule regardless of the
+    /// image bytes it's given, so `verify_plan` can be tested without a real JPEG decoder.
+    struct StubConverter {
+        schedule: Vec<(NaiveDate, u32)>,
+    }
+
+    impl Converter for StubConverter {
+        fn convert(&self) -> &'static str {
+            "stub"
+        }
+
+        fn regenerate_plan(&self, _image: &[u8]) -> Vec<(NaiveDate, u32)> {
+            self.schedule.clone()
+        }
+    }
+
+    /// Creates a random tempdir and db file to be used for the DataAccessor
+    fn create_random_db_loc() -> Option<PathBuf> {
+        let dir = tempfile::tempdir().unwrap();
+        let t_nano = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let db_path = dir.path().join(format!("gcontrib{t_nano}.db"));
+
+        Some(db_path)
+    }
+
+    #[test]
+    fn test_verify_plan_reports_drift_in_both_directions() {
+        let matching = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mismatched = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let stale = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+
+        let db_loc = create_random_db_loc().unwrap();
+        let da = DataAccessor::with_db(db_loc).unwrap();
+
+        let cd = CommitDict::from([(&matching, 2), (&mismatched, 3), (&stale, 4)]);
+        let txn = da.transaction().unwrap();
+        da.add_commit_plan(&txn, &cd).unwrap();
+        txn.commit().unwrap();
+
+        let image_bytes = vec![0xFFu8, 0xD8, 0xFF, 0xE0];
+        da.add_source_image("stub", 1, 1, image_bytes.len(), image_bytes.as_slice())
+            .unwrap();
+
+        let converter = StubConverter {
+            schedule: vec![(matching, 2), (mismatched, 5)],
+        };
+        let fc = FlowControl::new(converter, Committer::new("test".to_string()), da);
+
+        let drift = fc.verify_plan().unwrap();
+        assert_eq!(drift.len(), 2);
+
+        let regenerated_mismatch = drift.iter().find(|d| d.date == mismatched).unwrap();
+        assert_eq!(regenerated_mismatch.expected, 5);
+        assert_eq!(regenerated_mismatch.actual, 3);
+
+        let stale_persisted = drift.iter().find(|d| d.date == stale).unwrap();
+        assert_eq!(stale_persisted.expected, 0);
+        assert_eq!(stale_persisted.actual, 4);
+
+        assert!(!drift.iter().any(|d| d.date == matching));
+    }
+}
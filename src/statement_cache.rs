@@ -0,0 +1,69 @@
+//! A small LRU cache of prepared, already-reset statements keyed by SQL text, so a
+//! `DataAccessor` that runs the same handful of queries over and over (e.g. once per day in
+//! `FlowControl::run`) doesn't reopen the database file and re-parse SQL on every call.
+
+use std::collections::HashMap;
+
+use gcontributor::types::SqlResult;
+
+/// Caches prepared statements against one owned connection. Statements borrow the connection
+/// for their lifetime, which is why the connection lives behind a `Box`: its heap address is
+/// stable even if the `StatementCache` itself is moved, so extending a statement's borrow to
+/// `'static` below is sound as long as every statement is dropped before `conn` is. Rust drops
+/// struct fields in declaration order, so `entries`/`order` are declared before `conn` to
+/// guarantee that.
+pub struct StatementCache {
+    entries: HashMap<String, sqlite::Statement<'static>>,
+    order: Vec<String>,
+    capacity: usize,
+    conn: Box<sqlite::Connection>,
+}
+
+impl StatementCache {
+    pub fn new(conn: sqlite::Connection, capacity: usize) -> Self {
+        StatementCache {
+            entries: HashMap::new(),
+            order: Vec::new(),
+            capacity,
+            conn: Box::new(conn),
+        }
+    }
+
+    /// Returns a reset, ready-to-bind statement for `sql`, preparing and caching it the first
+    /// time it's seen and evicting the least-recently-used entry once `capacity` is exceeded.
+    pub fn prepare(&mut self, sql: &str) -> SqlResult<&mut sqlite::Statement<'static>> {
+        if self.entries.contains_key(sql) {
+            self.touch(sql);
+        } else {
+            if self.entries.len() >= self.capacity && !self.order.is_empty() {
+                let oldest = self.order.remove(0);
+                self.entries.remove(&oldest);
+            }
+            // SAFETY: see the struct doc comment - `conn`'s heap allocation outlives every
+            // statement prepared against it, since those statements live in `entries`, which
+            // is dropped before `conn` on both a normal drop and a `flush()`.
+            let conn: &'static sqlite::Connection = unsafe { &*(self.conn.as_ref() as *const sqlite::Connection) };
+            let stmt = conn.prepare(sql)?;
+            self.entries.insert(sql.to_string(), stmt);
+            self.order.push(sql.to_string());
+        }
+        let stmt = self.entries.get_mut(sql).expect("just inserted or touched above");
+        stmt.reset()?;
+        Ok(stmt)
+    }
+
+    /// Moves `sql` to the most-recently-used end of the eviction order
+    fn touch(&mut self, sql: &str) {
+        if let Some(pos) = self.order.iter().position(|cached| cached == sql) {
+            let entry = self.order.remove(pos);
+            self.order.push(entry);
+        }
+    }
+
+    /// Drops every cached statement, forcing the next `prepare` call for each SQL text to
+    /// re-prepare it from scratch
+    pub fn flush(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
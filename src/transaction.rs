@@ -0,0 +1,92 @@
+//! RAII transaction guard for `DataAccessor`.
+//!
+//! `Transaction` begins `BEGIN IMMEDIATE` as soon as it's constructed and rolls back in its
+//! `Drop` impl unless `commit` was called, so a `?` early-return partway through a write no
+//! longer leaves the connection sitting inside an open transaction.
+
+use gcontributor::error::SQLDataError;
+use gcontributor::types::SqlResult;
+
+/// Rejects savepoint names that aren't safe to interpolate directly into `SAVEPOINT`/`RELEASE
+/// SAVEPOINT`/`ROLLBACK TO SAVEPOINT` SQL text, since the `sqlite` crate has no way to bind an
+/// identifier as a parameter.
+fn validate_savepoint_name(name: &str) -> SqlResult<()> {
+    let mut chars = name.chars();
+    let starts_ok = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    let rest_ok = chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if starts_ok && rest_ok {
+        Ok(())
+    } else {
+        Err(SQLDataError::InvalidIdentifier(name.to_string()))
+    }
+}
+
+/// An open `BEGIN IMMEDIATE` transaction on a dedicated connection. Rolls back on drop unless
+/// `commit` is called first.
+pub struct Transaction {
+    conn: sqlite::Connection,
+    committed: bool,
+}
+
+impl Transaction {
+    /// Begins an immediate transaction on `conn`. `conn` should not be shared with anything
+    /// else for the lifetime of the returned `Transaction`.
+    pub(crate) fn begin(conn: sqlite::Connection) -> SqlResult<Self> {
+        conn.execute("BEGIN IMMEDIATE;")?;
+        Ok(Transaction { conn, committed: false })
+    }
+
+    /// The connection this transaction is running on, for preparing statements against.
+    pub fn connection(&self) -> &sqlite::Connection {
+        &self.conn
+    }
+
+    /// Commits the transaction. Consuming `self` here (rather than `&mut self`) means the
+    /// `Drop` impl still runs afterwards, but sees `committed = true` and skips the rollback.
+    pub fn commit(mut self) -> SqlResult<()> {
+        self.conn.execute("COMMIT;")?;
+        self.committed = true;
+        Ok(())
+    }
+
+    /// Opens a nested savepoint. The savepoint rolls back to itself on drop unless `release`
+    /// is called, without unwinding the outer transaction.
+    pub fn savepoint(&self, name: &str) -> SqlResult<Savepoint<'_>> {
+        validate_savepoint_name(name)?;
+        self.conn.execute(format!("SAVEPOINT {name};"))?;
+        Ok(Savepoint { conn: &self.conn, name: name.to_string(), released: false })
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = self.conn.execute("ROLLBACK;");
+        }
+    }
+}
+
+/// A nested `SAVEPOINT` scoped to a `Transaction`. Rolls back to the savepoint on drop unless
+/// `release` is called first.
+pub struct Savepoint<'a> {
+    conn: &'a sqlite::Connection,
+    name: String,
+    released: bool,
+}
+
+impl<'a> Savepoint<'a> {
+    /// Releases the savepoint, keeping everything written since it was opened.
+    pub fn release(mut self) -> SqlResult<()> {
+        self.conn.execute(format!("RELEASE SAVEPOINT {};", self.name))?;
+        self.released = true;
+        Ok(())
+    }
+}
+
+impl<'a> Drop for Savepoint<'a> {
+    fn drop(&mut self) {
+        if !self.released {
+            let _ = self.conn.execute(format!("ROLLBACK TO SAVEPOINT {};", self.name));
+        }
+    }
+}